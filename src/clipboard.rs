@@ -2,6 +2,23 @@ use std::borrow::Cow;
 
 use serde::{Deserialize, Serialize};
 
+/// Which clipboard a payload belongs to: the regular clipboard (ctrl-c/ctrl-v)
+/// or the X11/Wayland primary selection (middle-click paste).
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Selection {
+    Regular,
+    Primary,
+}
+
+impl From<Selection> for arboard::LinuxClipboardKind {
+    fn from(value: Selection) -> Self {
+        match value {
+            Selection::Regular => arboard::LinuxClipboardKind::Clipboard,
+            Selection::Primary => arboard::LinuxClipboardKind::Primary,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, PartialEq)]
 pub struct Image<'a> {
     pub width: usize,
@@ -29,51 +46,227 @@ impl<'a> From<arboard::ImageData<'a>> for Image<'a> {
     }
 }
 
+/// Metadata for a single file offered through the clipboard's file-list
+/// target. Modeled on RDP's `FileContentsRequest`/`FileContentsResponse`:
+/// metadata is advertised up front, and the actual bytes are fetched
+/// afterwards, file by file, via [`Clipboard::read_file`].
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+pub struct FileMeta {
+    pub name: String,
+    pub size: u64,
+}
+
+pub(crate) fn encode_png(image: &Image<'static>) -> anyhow::Result<Vec<u8>> {
+    use image::ImageEncoder as _;
+    let mut bytes = Vec::new();
+    image::codecs::png::PngEncoder::new(&mut bytes)
+        .write_image(
+            image.bytes.as_ref(),
+            image.width as u32,
+            image.height as u32,
+            image::ExtendedColorType::Rgba8,
+        )
+        .map_err(|e| anyhow::anyhow!("Encoding error: {e}"))?;
+    Ok(bytes)
+}
+
+pub(crate) fn decode_png(bytes: &[u8]) -> anyhow::Result<Image<'static>> {
+    let img = image::load_from_memory(bytes)?.into_rgba8();
+    let (width, height) = img.dimensions();
+    Ok(Image {
+        width: width as usize,
+        height: height as usize,
+        bytes: img.into_raw().into(),
+    })
+}
+
+/// Clears embedded metadata (EXIF GPS/camera data, timestamps, ...) from an
+/// image payload before it leaves the machine. PNG is re-encoded through
+/// the same RGBA decode/encode round trip `get_offers`/`set_offers` already
+/// use elsewhere, which only ever carries pixels and so drops any metadata
+/// chunks for free. JPEG and WebP keep their original (lossy) compression
+/// instead of being re-encoded, so metadata is stripped losslessly by piping
+/// the bytes through `exiftool -all= - -out -`.
+///
+/// This is a privacy guarantee, so it fails closed: any mime type we don't
+/// know how to strip, and any mime type whose stripping step itself fails
+/// (e.g. `exiftool` is missing), is an error rather than a fallback to the
+/// original, still metadata-bearing bytes. Callers must drop the offer
+/// rather than sync it unstripped.
+pub(crate) fn strip_image_metadata(mime: &str, bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    match mime {
+        "image/png" => decode_png(bytes).and_then(|img| encode_png(&img)),
+        "image/jpeg" | "image/jpg" | "image/webp" => strip_with_exiftool(bytes),
+        _ => anyhow::bail!("no metadata-stripping step for mime type {mime}"),
+    }
+}
+
+fn strip_with_exiftool(bytes: &[u8]) -> anyhow::Result<Vec<u8>> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let mut child = Command::new("exiftool")
+        .args(["-all=", "-", "-out", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("stdin was requested as piped");
+    let input = bytes.to_vec();
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    writer
+        .join()
+        .map_err(|_| anyhow::anyhow!("exiftool stdin writer thread panicked"))??;
+    anyhow::ensure!(
+        output.status.success(),
+        "exiftool exited with {}",
+        output.status
+    );
+    Ok(output.stdout)
+}
+
 pub trait Clipboard {
-    fn get_text(&mut self) -> anyhow::Result<String>;
-    fn get_image(&mut self) -> anyhow::Result<Image<'static>>;
-    fn set_text(&mut self, data: String) -> anyhow::Result<()>;
-    fn set_image(&mut self, data: Image<'static>) -> anyhow::Result<()>;
+    fn get_text(&mut self, selection: Selection) -> anyhow::Result<String>;
+    fn get_image(&mut self, selection: Selection) -> anyhow::Result<Image<'static>>;
+    fn set_text(&mut self, data: String, selection: Selection) -> anyhow::Result<()>;
+    fn set_image(&mut self, data: Image<'static>, selection: Selection) -> anyhow::Result<()>;
+
+    /// The MIME representations of the current copy that this backend can
+    /// read (e.g. `text/plain`, `text/html`, `image/png`, all describing the
+    /// same copy), so a receiver can pick whichever it understands. The
+    /// arboard backend returns a single offer: the text, or else the image.
+    fn get_offers(&mut self, selection: Selection) -> anyhow::Result<Vec<(String, Vec<u8>)>>;
+    /// Places several MIME representations of the same payload on the
+    /// clipboard at once. The arboard backend keeps only one of them: the
+    /// first `text/` offer, or else the first `image/` offer.
+    fn set_offers(
+        &mut self,
+        offers: Vec<(String, Vec<u8>)>,
+        selection: Selection,
+    ) -> anyhow::Result<()>;
+
+    /// Lists the files currently offered by the clipboard's file-list
+    /// target, without reading their contents.
+    fn list_files(&mut self, selection: Selection) -> anyhow::Result<Vec<FileMeta>>;
+    /// Reads the contents of the `index`-th file of the file list on the
+    /// clipboard at the time of the call, returning its name and bytes. The
+    /// list is fetched again, not taken from an earlier `list_files` call.
+    fn read_file(&mut self, selection: Selection, index: usize) -> anyhow::Result<(String, Vec<u8>)>;
+    /// Places a list of files (with their contents) on the clipboard's
+    /// file-list target.
+    fn set_files(
+        &mut self,
+        files: Vec<(FileMeta, Vec<u8>)>,
+        selection: Selection,
+    ) -> anyhow::Result<()>;
 }
 
 impl Clipboard for arboard::Clipboard {
-    fn get_text(&mut self) -> anyhow::Result<String> {
-        arboard::Clipboard::get_text(self).map_err(Into::into)
+    fn get_text(&mut self, selection: Selection) -> anyhow::Result<String> {
+        use arboard::GetExtLinux;
+        self.get()
+            .clipboard(selection.into())
+            .text()
+            .map_err(Into::into)
     }
 
-    fn get_image(&mut self) -> anyhow::Result<Image<'static>> {
-        arboard::Clipboard::get_image(self)
+    fn get_image(&mut self, selection: Selection) -> anyhow::Result<Image<'static>> {
+        use arboard::GetExtLinux;
+        self.get()
+            .clipboard(selection.into())
+            .image()
             .map_err(Into::into)
             .map(Image::from)
     }
 
-    fn set_text(&mut self, data: String) -> anyhow::Result<()> {
-        arboard::Clipboard::set_text(self, data).map_err(Into::into)
+    fn set_text(&mut self, data: String, selection: Selection) -> anyhow::Result<()> {
+        use arboard::SetExtLinux;
+        self.set()
+            .clipboard(selection.into())
+            .text(data)
+            .map_err(Into::into)
+    }
+
+    fn set_image(&mut self, data: Image<'static>, selection: Selection) -> anyhow::Result<()> {
+        use arboard::SetExtLinux;
+        self.set()
+            .clipboard(selection.into())
+            .image(data.into())
+            .map_err(Into::into)
+    }
+
+    fn get_offers(&mut self, selection: Selection) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+        if let Ok(text) = self.get_text(selection) {
+            return Ok(vec![("text/plain".to_string(), text.into_bytes())]);
+        }
+        let image = self.get_image(selection)?;
+        Ok(vec![("image/png".to_string(), encode_png(&image)?)])
+    }
+
+    fn set_offers(
+        &mut self,
+        offers: Vec<(String, Vec<u8>)>,
+        selection: Selection,
+    ) -> anyhow::Result<()> {
+        if let Some((_, bytes)) = offers.iter().find(|(mime, _)| mime.starts_with("text/")) {
+            return self.set_text(String::from_utf8_lossy(bytes).into_owned(), selection);
+        }
+        if let Some((_, bytes)) = offers.iter().find(|(mime, _)| mime.starts_with("image/")) {
+            return self.set_image(decode_png(bytes)?, selection);
+        }
+        anyhow::bail!("no offer in a MIME type this backend understands")
+    }
+
+    fn list_files(&mut self, _selection: Selection) -> anyhow::Result<Vec<FileMeta>> {
+        anyhow::bail!("file transfer is not supported on this backend")
+    }
+
+    fn read_file(&mut self, _selection: Selection, _index: usize) -> anyhow::Result<(String, Vec<u8>)> {
+        anyhow::bail!("file transfer is not supported on this backend")
     }
 
-    fn set_image(&mut self, data: Image<'static>) -> anyhow::Result<()> {
-        arboard::Clipboard::set_image(self, data.into()).map_err(Into::into)
+    fn set_files(
+        &mut self,
+        _files: Vec<(FileMeta, Vec<u8>)>,
+        _selection: Selection,
+    ) -> anyhow::Result<()> {
+        anyhow::bail!("file transfer is not supported on this backend")
     }
 }
 
 pub struct WaylandClipboard;
 
+fn paste_selection(selection: Selection) -> wl_clipboard_rs::paste::ClipboardType {
+    match selection {
+        Selection::Regular => wl_clipboard_rs::paste::ClipboardType::Regular,
+        Selection::Primary => wl_clipboard_rs::paste::ClipboardType::Primary,
+    }
+}
+
+fn copy_selection(selection: Selection) -> wl_clipboard_rs::copy::ClipboardType {
+    match selection {
+        Selection::Regular => wl_clipboard_rs::copy::ClipboardType::Regular,
+        Selection::Primary => wl_clipboard_rs::copy::ClipboardType::Primary,
+    }
+}
+
 impl Clipboard for WaylandClipboard {
-    fn get_text(&mut self) -> anyhow::Result<String> {
+    fn get_text(&mut self, selection: Selection) -> anyhow::Result<String> {
         use std::io::Read;
         use wl_clipboard_rs::paste::*;
         let (mut pipe, _) =
-            get_contents(ClipboardType::Regular, Seat::Unspecified, MimeType::Text)?;
+            get_contents(paste_selection(selection), Seat::Unspecified, MimeType::Text)?;
         let mut contents = vec![];
         pipe.read_to_end(&mut contents)?;
         Ok(String::from_utf8_lossy(&contents).into_owned())
     }
 
-    fn get_image(&mut self) -> anyhow::Result<Image<'static>> {
+    fn get_image(&mut self, selection: Selection) -> anyhow::Result<Image<'static>> {
         use std::io::Read;
         use wl_clipboard_rs::paste::*;
         let (mut pipe, mime) =
-            get_contents(ClipboardType::Regular, Seat::Unspecified, MimeType::Any)?;
+            get_contents(paste_selection(selection), Seat::Unspecified, MimeType::Any)?;
         let format = match mime.as_str() {
             "image/png" => image::ImageFormat::Png,
             "image/gif" => image::ImageFormat::Gif,
@@ -95,15 +288,15 @@ impl Clipboard for WaylandClipboard {
         })
     }
 
-    fn set_text(&mut self, data: String) -> anyhow::Result<()> {
+    fn set_text(&mut self, data: String, selection: Selection) -> anyhow::Result<()> {
         use wl_clipboard_rs::copy::*;
         let mut opts = Options::new();
-        opts.clipboard(ClipboardType::Regular);
+        opts.clipboard(copy_selection(selection));
         opts.copy(Source::Bytes(data.into_bytes().into()), MimeType::Text)?;
         Ok(())
     }
 
-    fn set_image(&mut self, data: Image<'static>) -> anyhow::Result<()> {
+    fn set_image(&mut self, data: Image<'static>, selection: Selection) -> anyhow::Result<()> {
         use image::ImageEncoder as _;
         use wl_clipboard_rs::copy::*;
 
@@ -123,11 +316,165 @@ impl Clipboard for WaylandClipboard {
             .map_err(|e| anyhow::anyhow!("Encoding error: {e}"))?;
 
         let mut opts = Options::new();
-        opts.clipboard(ClipboardType::Regular);
+        opts.clipboard(copy_selection(selection));
         opts.copy(
             Source::Bytes(png_bytes.into()),
             MimeType::Specific("image/png".to_string()),
         )?;
         Ok(())
     }
+
+    fn get_offers(&mut self, selection: Selection) -> anyhow::Result<Vec<(String, Vec<u8>)>> {
+        use std::io::Read;
+        use wl_clipboard_rs::paste::*;
+
+        const SUPPORTED: &[&str] = &[
+            "text/plain;charset=utf-8",
+            "text/plain",
+            "text/html",
+            "image/png",
+        ];
+
+        let available = get_mime_types(paste_selection(selection), Seat::Unspecified)?;
+        let mut offers = Vec::new();
+        for &mime in SUPPORTED {
+            if !available.contains(mime) {
+                continue;
+            }
+            let (mut pipe, _) = get_contents(
+                paste_selection(selection),
+                Seat::Unspecified,
+                MimeType::Specific(mime.to_string()),
+            )?;
+            let mut bytes = vec![];
+            pipe.read_to_end(&mut bytes)?;
+            offers.push((mime.to_string(), bytes));
+        }
+        Ok(offers)
+    }
+
+    fn set_offers(
+        &mut self,
+        offers: Vec<(String, Vec<u8>)>,
+        selection: Selection,
+    ) -> anyhow::Result<()> {
+        use wl_clipboard_rs::copy::*;
+
+        anyhow::ensure!(!offers.is_empty(), "no MIME offers to copy");
+        let sources = offers
+            .into_iter()
+            .map(|(mime, bytes)| MimeSource {
+                source: Source::Bytes(bytes.into()),
+                mime_type: MimeType::Specific(mime),
+            })
+            .collect();
+
+        let mut opts = Options::new();
+        opts.clipboard(copy_selection(selection));
+        opts.copy_multi(sources)?;
+        Ok(())
+    }
+
+    fn list_files(&mut self, selection: Selection) -> anyhow::Result<Vec<FileMeta>> {
+        uri_list(selection)?
+            .into_iter()
+            .map(|path| {
+                let size = std::fs::metadata(&path)?.len();
+                Ok(FileMeta {
+                    name: file_name(&path),
+                    size,
+                })
+            })
+            .collect()
+    }
+
+    fn read_file(
+        &mut self,
+        selection: Selection,
+        index: usize,
+    ) -> anyhow::Result<(String, Vec<u8>)> {
+        let path = uri_list(selection)?
+            .into_iter()
+            .nth(index)
+            .ok_or_else(|| anyhow::anyhow!("no file at index {index}"))?;
+        Ok((file_name(&path), std::fs::read(&path)?))
+    }
+
+    fn set_files(
+        &mut self,
+        files: Vec<(FileMeta, Vec<u8>)>,
+        selection: Selection,
+    ) -> anyhow::Result<()> {
+        use wl_clipboard_rs::copy::*;
+
+        let dir = std::env::temp_dir().join(format!("clipport-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let mut uri_list = String::new();
+        for (meta, bytes) in files {
+            let path = dir.join(&meta.name);
+            std::fs::write(&path, &bytes)?;
+            uri_list.push_str("file://");
+            uri_list.push_str(&path.to_string_lossy());
+            uri_list.push_str("\r\n");
+        }
+
+        let mut opts = Options::new();
+        opts.clipboard(copy_selection(selection));
+        opts.copy(
+            Source::Bytes(uri_list.into_bytes().into()),
+            MimeType::Specific("text/uri-list".to_string()),
+        )?;
+        Ok(())
+    }
+}
+
+/// Fetches and parses the `text/uri-list` target into local file paths.
+fn uri_list(selection: Selection) -> anyhow::Result<Vec<std::path::PathBuf>> {
+    use std::io::Read;
+    use wl_clipboard_rs::paste::*;
+
+    let (mut pipe, _) = get_contents(
+        paste_selection(selection),
+        Seat::Unspecified,
+        MimeType::Specific("text/uri-list".to_string()),
+    )?;
+    let mut contents = vec![];
+    pipe.read_to_end(&mut contents)?;
+    String::from_utf8_lossy(&contents)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(uri_to_path)
+        .collect()
+}
+
+fn uri_to_path(uri: &str) -> anyhow::Result<std::path::PathBuf> {
+    let path = uri
+        .strip_prefix("file://")
+        .ok_or_else(|| anyhow::anyhow!("unsupported URI scheme: {uri}"))?;
+    Ok(std::path::PathBuf::from(percent_decode(path)))
+}
+
+fn file_name(path: &std::path::Path) -> String {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(byte) = u8::from_str_radix(&s[i + 1..i + 3], 16) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
 }
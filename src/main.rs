@@ -1,106 +1,397 @@
 mod clipboard;
+mod cliprdr;
+mod tls;
 
 use clap::Parser;
-use derive_more::derive::From;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, time::Duration};
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::{
-        tcp::{ReadHalf, WriteHalf},
-        TcpListener, TcpStream,
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
     },
+    time::Duration,
+};
+use tokio::{
+    io::{split, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadHalf, WriteHalf},
+    net::{TcpListener, TcpStream},
 };
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+
+use self::clipboard::{Clipboard, FileMeta, Selection, WaylandClipboard};
+use self::tls::TlsArgs;
+
+/// A clipboard payload, tagged with the selection it belongs to: either a
+/// set of MIME representations of the same copy (text/plain, text/html,
+/// image/png, ...), or a list of files whose contents are fetched
+/// afterwards via [`Message::FileContentsRequest`].
+#[derive(Serialize, Deserialize, PartialEq, Clone)]
+pub(crate) enum CbData {
+    Offers(Selection, Vec<(String, Vec<u8>)>),
+    Files(Selection, Vec<FileMeta>),
+}
+
+impl CbData {
+    fn selection(&self) -> Selection {
+        match self {
+            CbData::Offers(selection, _) => *selection,
+            CbData::Files(selection, _) => *selection,
+        }
+    }
+}
+
+fn hash_of<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Per-selection, per-kind content hashes shared between a connection's
+/// input and output tasks. `handle_input` records the hash of whatever it
+/// just wrote to the local clipboard; `handle_output` skips re-sending a
+/// locally-read payload whose hash matches, which is what breaks the
+/// cross-machine echo loop (`handle_input` writes a remote payload locally,
+/// `handle_output` would otherwise immediately read it back and bounce it
+/// to the peer). Offers and files are tracked separately so an update to
+/// one never masks a pending update to the other.
+#[derive(Default)]
+pub(crate) struct DedupState {
+    offers: HashMap<Selection, AtomicU64>,
+    files: HashMap<Selection, AtomicU64>,
+}
+
+impl DedupState {
+    pub(crate) fn new(selections: &[Selection]) -> Self {
+        Self {
+            offers: selections.iter().map(|s| (*s, AtomicU64::new(0))).collect(),
+            files: selections.iter().map(|s| (*s, AtomicU64::new(0))).collect(),
+        }
+    }
+
+    fn record_offers(&self, selection: Selection, offers: &[(String, Vec<u8>)]) {
+        if let Some(hash) = self.offers.get(&selection) {
+            hash.store(hash_of(&offers), Ordering::Relaxed);
+        }
+    }
 
-use self::clipboard::{Clipboard, Image, WaylandClipboard};
+    fn record_files(&self, selection: Selection, files: &[FileMeta]) {
+        if let Some(hash) = self.files.get(&selection) {
+            hash.store(hash_of(&files), Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn is_echo_offers(&self, selection: Selection, offers: &[(String, Vec<u8>)]) -> bool {
+        self.offers
+            .get(&selection)
+            .is_some_and(|hash| hash.load(Ordering::Relaxed) == hash_of(&offers))
+    }
+
+    fn is_echo_files(&self, selection: Selection, files: &[FileMeta]) -> bool {
+        self.files
+            .get(&selection)
+            .is_some_and(|hash| hash.load(Ordering::Relaxed) == hash_of(&files))
+    }
+}
 
-#[derive(Serialize, Deserialize, PartialEq, From)]
-enum CbData {
-    Text(String),
-    Image(Image<'static>),
+/// Applies a clipboard offer update received from a remote peer to the local
+/// clipboard. File lists are handled separately by [`handle_input`], since
+/// materializing them requires a `FileContentsRequest`/`Response` round trip,
+/// so callers only ever pass a `CbData::Offers`.
+pub(crate) fn apply_remote(
+    clip: &mut dyn Clipboard,
+    selection: Selection,
+    offers: Vec<(String, Vec<u8>)>,
+    dedup: &DedupState,
+) {
+    if let Err(e) = clip.set_offers(offers.clone(), selection) {
+        log::error!("{e}");
+    } else {
+        dedup.record_offers(selection, &offers);
+    }
 }
 
 impl std::fmt::Display for CbData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match &self {
-            CbData::Text(str) => f.write_str(str),
-            CbData::Image(img) => f.write_str(&format!("Image {}x{}", img.width, img.height)),
+            CbData::Offers(_, offers) => {
+                let mimes: Vec<&str> = offers.iter().map(|(mime, _)| mime.as_str()).collect();
+                write!(f, "Offers [{}]", mimes.join(", "))
+            }
+            CbData::Files(_, files) => write!(f, "Files ({} file(s))", files.len()),
         }
     }
 }
 
-async fn handle_output(
-    peer: impl Display,
-    mut stream: WriteHalf<'_>,
-    wayland: bool,
-) -> anyhow::Result<()> {
-    let mut clip: Box<dyn Clipboard + Send> = if wayland {
+/// Messages exchanged over the sync connection: clipboard updates, plus the
+/// request/response pair used to fetch file contents on demand once a
+/// `CbData::Files` listing has been announced.
+#[derive(Serialize, Deserialize)]
+enum Message {
+    Clipboard(CbData),
+    FileContentsRequest(Selection, u32),
+    FileContentsResponse(Selection, u32, String, Vec<u8>),
+}
+
+pub(crate) fn make_clipboard(wayland: bool) -> Box<dyn Clipboard + Send> {
+    if wayland {
         Box::new(WaylandClipboard)
     } else {
         Box::new(arboard::Clipboard::new().unwrap())
+    }
+}
+
+pub(crate) fn read_cb_data(
+    clip: &mut dyn Clipboard,
+    selection: Selection,
+    strip_image_metadata: bool,
+) -> Option<CbData> {
+    if let Ok(files) = clip.list_files(selection).inspect_err(log) {
+        if !files.is_empty() {
+            return Some(CbData::Files(selection, files));
+        }
+    }
+    let mut offers = clip.get_offers(selection).inspect_err(log).ok()?;
+    if strip_image_metadata {
+        // Fails closed: an offer we can't strip is dropped rather than sent
+        // with its embedded metadata intact.
+        offers.retain_mut(|(mime, bytes)| {
+            if !mime.starts_with("image/") {
+                return true;
+            }
+            match clipboard::strip_image_metadata(mime, bytes) {
+                Ok(stripped) => {
+                    *bytes = stripped;
+                    true
+                }
+                Err(e) => {
+                    log::warn!("Dropping {mime} offer instead of syncing it with embedded metadata intact: {e}");
+                    false
+                }
+            }
+        });
+    }
+    if offers.is_empty() {
+        return None;
+    }
+    Some(CbData::Offers(selection, offers))
+}
+
+/// Writes `msg` as a length-prefixed frame: a big-endian `u32` byte count
+/// followed by its postcard encoding. Paired with [`read_frame`], this lets
+/// the reader size its buffer off the declared length instead of growing
+/// one incrementally and re-parsing it on every partial read.
+///
+/// If the encoded frame exceeds `max_frame_size`, the peer's [`read_frame`]
+/// would discard it anyway, so it's skipped here rather than sent: a single
+/// oversized clipboard payload is logged and dropped instead of being
+/// written to the wire, where the receiver would otherwise have to read
+/// all of its bytes only to throw them away.
+async fn write_frame<W: AsyncWrite + Unpin>(
+    stream: &mut W,
+    msg: &Message,
+    max_frame_size: u32,
+) -> anyhow::Result<()> {
+    let body = postcard::to_stdvec(msg)?;
+    let len: u32 = match body.len().try_into() {
+        Ok(len) => len,
+        Err(_) => {
+            log::warn!("dropping a frame of {} bytes: too large to send", body.len());
+            return Ok(());
+        }
     };
-    let mut last_cb_data: Option<CbData> = None;
+    if len > max_frame_size {
+        log::warn!("dropping a frame of {len} bytes: exceeds max-frame-size ({max_frame_size})");
+        return Ok(());
+    }
+    stream.write_all(&len.to_be_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Largest slice read from the wire in one `AsyncRead` call while
+/// [`read_chunked`] fills a frame body's buffer. This only bounds how much
+/// a single lying length prefix can make `read_exact` over-allocate before
+/// any bytes have actually arrived to back it up; the frame as a whole is
+/// still fully materialized in memory (twice — once here, again when
+/// `postcard::from_bytes` decodes it into an owned `Message`), so this is
+/// length-prefixed framing with a capped-growth read loop, not an
+/// AsyncRead-style stream that a huge clipboard payload could pass through
+/// without ever being held whole. Doing that would mean reworking the
+/// `Clipboard` trait (currently whole-`Vec<u8>` get/set only) and
+/// `postcard`'s non-streaming decode, which is out of scope here.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads exactly `len` bytes, growing `body` in `READ_CHUNK_SIZE` pieces as
+/// they arrive instead of allocating the whole length up front. `body` is
+/// still fully resident by the time this returns; see [`READ_CHUNK_SIZE`]
+/// for why that's an intentional scope limit rather than an oversight.
+async fn read_chunked<R: AsyncRead + Unpin>(
+    stream: &mut R,
+    len: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut body = Vec::with_capacity(len.min(READ_CHUNK_SIZE));
+    while body.len() < len {
+        let take = (len - body.len()).min(READ_CHUNK_SIZE);
+        let start = body.len();
+        body.resize(start + take, 0);
+        stream.read_exact(&mut body[start..]).await?;
+    }
+    Ok(body)
+}
+
+/// Reads and drops `len` bytes in `READ_CHUNK_SIZE` pieces, for a frame
+/// [`read_frame`] has already decided to reject. Reuses one fixed-size
+/// buffer instead of [`read_chunked`]'s growing `Vec`, since the bytes
+/// themselves are never needed — only advancing the stream past them so
+/// the next frame's length prefix is read from the right offset.
+async fn discard_chunked<R: AsyncRead + Unpin>(stream: &mut R, mut len: usize) -> anyhow::Result<()> {
+    let mut buf = [0u8; READ_CHUNK_SIZE];
+    while len > 0 {
+        let take = len.min(READ_CHUNK_SIZE);
+        stream.read_exact(&mut buf[..take]).await?;
+        len -= take;
+    }
+    Ok(())
+}
+
+/// Reads one length-prefixed frame written by [`write_frame`]. Returns
+/// `Ok(None)` on a clean EOF between frames.
+///
+/// A frame whose declared length exceeds `max_frame_size` is read off the
+/// wire and discarded rather than rejected outright: the length prefix is
+/// trustworthy framing metadata (it's how we know where the next frame
+/// starts), so a hostile or confused peer can't make us allocate an
+/// unbounded buffer by sending a huge length, but a single legitimate
+/// over-limit payload (e.g. a clipboard image above the configured cap)
+/// shouldn't tear down the whole sync connection either. The loop keeps
+/// reading frames until it finds one within the limit or the stream ends.
+async fn read_frame<R: AsyncRead + Unpin>(
+    stream: &mut R,
+    max_frame_size: u32,
+) -> anyhow::Result<Option<Message>> {
     loop {
-        tokio::time::sleep(Duration::from_millis(500)).await;
-        let new_cb_data = if let Ok(text) = clip.get_text().inspect_err(log) {
-            Some(text.into())
-        } else if let Ok(img) = clip.get_image().inspect_err(log) {
-            Some(img.into())
-        } else {
-            None
-        };
-        if new_cb_data != last_cb_data {
-            last_cb_data = new_cb_data;
-            if let Some(data) = &last_cb_data {
-                log::trace!("Sending to {peer}: {data}");
-                stream.write_all(&postcard::to_stdvec(data)?).await?;
-                stream.flush().await?
+        let mut len_buf = [0u8; 4];
+        if let Err(e) = stream.read_exact(&mut len_buf).await {
+            return match e.kind() {
+                std::io::ErrorKind::UnexpectedEof => Ok(None),
+                _ => Err(e.into()),
+            };
+        }
+        let len = u32::from_be_bytes(len_buf);
+        if len > max_frame_size {
+            log::warn!("dropping an oversized frame ({len} bytes, limit {max_frame_size})");
+            discard_chunked(stream, len as usize).await?;
+            continue;
+        }
+        let body = read_chunked(stream, len as usize).await?;
+        return Ok(Some(postcard::from_bytes(&body)?));
+    }
+}
+
+async fn handle_output<W: AsyncWrite + Unpin>(
+    peer: impl Display,
+    mut stream: WriteHalf<W>,
+    wayland: bool,
+    selections: &[Selection],
+    dedup: Arc<DedupState>,
+    strip_image_metadata: bool,
+    max_frame_size: u32,
+    mut outgoing: tokio::sync::mpsc::UnboundedReceiver<Message>,
+) -> anyhow::Result<()> {
+    let mut clip = make_clipboard(wayland);
+    let mut last_cb_data: HashMap<Selection, Option<CbData>> =
+        selections.iter().map(|s| (*s, None)).collect();
+    let mut tick = tokio::time::interval(Duration::from_millis(500));
+    loop {
+        tokio::select! {
+            _ = tick.tick() => {
+                for &selection in selections {
+                    let new_cb_data = read_cb_data(&mut *clip, selection, strip_image_metadata);
+                    let last = last_cb_data.get_mut(&selection).expect("all selections tracked");
+                    if new_cb_data != *last {
+                        *last = new_cb_data;
+                        let is_echo = match last {
+                            Some(CbData::Offers(s, offers)) => dedup.is_echo_offers(*s, offers),
+                            Some(CbData::Files(s, files)) => dedup.is_echo_files(*s, files),
+                            None => false,
+                        };
+                        if is_echo {
+                            log::trace!("Skipping echo back to {peer}");
+                        } else if let Some(data) = last {
+                            log::trace!("Sending to {peer}: {data}");
+                            let msg = Message::Clipboard(data.clone());
+                            write_frame(&mut stream, &msg, max_frame_size).await?;
+                        }
+                    }
+                }
+            }
+            msg = outgoing.recv() => {
+                let Some(msg) = msg else { return Ok(()) };
+                write_frame(&mut stream, &msg, max_frame_size).await?;
             }
         }
     }
 }
 
-async fn handle_input(
+async fn handle_input<R: AsyncRead + Unpin>(
     peer: impl Display,
-    mut stream: ReadHalf<'_>,
+    mut stream: ReadHalf<R>,
     wayland: bool,
+    dedup: Arc<DedupState>,
+    max_frame_size: u32,
+    outgoing: tokio::sync::mpsc::UnboundedSender<Message>,
 ) -> anyhow::Result<()> {
-    let mut clip: Box<dyn Clipboard + Send> = if wayland {
-        Box::new(WaylandClipboard)
-    } else {
-        Box::new(arboard::Clipboard::new().unwrap())
-    };
-    let mut buf = vec![];
+    let mut clip = make_clipboard(wayland);
+    // Per-selection bookkeeping for in-flight file transfers: the metadata
+    // most recently announced, and the bytes collected so far for it.
+    let mut pending_files: HashMap<Selection, Vec<FileMeta>> = HashMap::new();
+    let mut collected: HashMap<Selection, Vec<Option<Vec<u8>>>> = HashMap::new();
     loop {
-        let x = loop {
-            let n = stream.read_buf(&mut buf).await?;
-            if matches!(n, 0) {
-                return Ok(());
+        let Some(msg) = read_frame(&mut stream, max_frame_size).await? else {
+            return Ok(());
+        };
+        match msg {
+            Message::Clipboard(CbData::Offers(selection, offers)) => {
+                log::trace!("Got clipboard from {peer}: {} offer(s)", offers.len());
+                apply_remote(&mut *clip, selection, offers, &dedup);
             }
-            match postcard::take_from_bytes::<CbData>(&buf) {
-                Ok((x, rest)) => {
-                    buf = rest.to_vec();
-                    break x;
+            Message::Clipboard(CbData::Files(selection, metas)) => {
+                log::trace!("Got {} file(s) from {peer}", metas.len());
+                for index in 0..metas.len() {
+                    let _ = outgoing.send(Message::FileContentsRequest(selection, index as u32));
                 }
-                Err(postcard::Error::DeserializeUnexpectedEnd) => {
-                    continue;
-                }
-                Err(err) => return Err(err.into()),
-            };
-        };
-        log::trace!("Got clipboard from {}: {x}", peer);
-        match x {
-            CbData::Text(text) => {
-                if clip.get_text().inspect_err(log).ok().as_ref() != Some(&text) {
-                    if let Err(e) = clip.set_text(text) {
-                        log::error!("{e}");
+                collected.insert(selection, vec![None; metas.len()]);
+                pending_files.insert(selection, metas);
+            }
+            Message::FileContentsRequest(selection, index) => {
+                match clip.read_file(selection, index as usize) {
+                    Ok((name, bytes)) => {
+                        let _ = outgoing.send(Message::FileContentsResponse(
+                            selection, index, name, bytes,
+                        ));
                     }
+                    Err(e) => log::error!("{e}"),
                 }
             }
-            CbData::Image(image_data) => {
-                if clip.get_image().inspect_err(log).ok().as_ref() != Some(&image_data) {
-                    if let Err(e) = clip.set_image(image_data) {
+            Message::FileContentsResponse(selection, index, _name, bytes) => {
+                let Some(slots) = collected.get_mut(&selection) else {
+                    continue;
+                };
+                if let Some(slot) = slots.get_mut(index as usize) {
+                    *slot = Some(bytes);
+                }
+                if slots.iter().all(Option::is_some) {
+                    let metas = pending_files.remove(&selection).unwrap_or_default();
+                    let slots = collected.remove(&selection).unwrap_or_default();
+                    let files: Vec<_> = metas.into_iter().zip(slots.into_iter().flatten()).collect();
+                    let metas: Vec<_> = files.iter().map(|(meta, _)| meta.clone()).collect();
+                    if let Err(e) = clip.set_files(files, selection) {
                         log::error!("{e}");
+                    } else {
+                        dedup.record_files(selection, &metas);
                     }
                 }
             }
@@ -119,6 +410,41 @@ struct Args {
     command: Cmd,
     #[arg(long)]
     wayland: bool,
+    /// Mirror the primary selection (middle-click paste) instead of the regular clipboard.
+    /// Ignored by the `rdp` subcommand, which always uses the regular clipboard.
+    #[arg(long, conflicts_with = "both")]
+    primary: bool,
+    /// Mirror both the regular clipboard and the primary selection.
+    /// Ignored by the `rdp` subcommand, which always uses the regular clipboard.
+    #[arg(long)]
+    both: bool,
+    /// Strip EXIF/metadata (GPS, camera serials, timestamps) from images
+    /// before syncing them. PNG is stripped by re-encoding from pixels;
+    /// JPEG and WebP are stripped losslessly via `exiftool`, which must be
+    /// on `PATH`.
+    #[arg(long)]
+    strip_image_metadata: bool,
+    /// Drop clipboard frames larger than this many bytes instead of sending
+    /// or applying them, guarding against a hostile or confused peer and
+    /// against oversized payloads (e.g. a very large image) that would
+    /// otherwise have to be sent at all. A dropped frame only costs that one
+    /// clipboard update; the connection stays up.
+    #[arg(long, default_value_t = 64 * 1024 * 1024)]
+    max_frame_size: u32,
+    #[command(flatten)]
+    tls: TlsArgs,
+}
+
+impl Args {
+    fn selections(&self) -> Vec<Selection> {
+        if self.both {
+            vec![Selection::Regular, Selection::Primary]
+        } else if self.primary {
+            vec![Selection::Primary]
+        } else {
+            vec![Selection::Regular]
+        }
+    }
 }
 
 #[derive(clap::Subcommand, Clone)]
@@ -130,16 +456,37 @@ enum Cmd {
     Client {
         host: String,
     },
+    /// Act as an RDP client and bridge the local clipboard to that session's
+    /// CLIPRDR channel, instead of talking to another clipport instance.
+    Rdp {
+        /// `host` or `host:port`; defaults to port 3389 if omitted.
+        host: String,
+        #[arg(long)]
+        username: String,
+        #[arg(long)]
+        password: String,
+        #[arg(long)]
+        domain: Option<String>,
+    },
 }
 
-async fn handle_client(peer: impl Display, mut stream: TcpStream, wayland: bool) {
-    let (read, write) = stream.split();
+async fn handle_client<S: AsyncRead + AsyncWrite + Unpin>(
+    peer: impl Display,
+    stream: S,
+    wayland: bool,
+    selections: Vec<Selection>,
+    strip_image_metadata: bool,
+    max_frame_size: u32,
+) {
+    let (read, write) = split(stream);
+    let (outgoing_tx, outgoing_rx) = tokio::sync::mpsc::unbounded_channel();
+    let dedup = Arc::new(DedupState::new(&selections));
     let err = tokio::select! {
-        err = handle_input(&peer, read, wayland) => {
+        err = handle_input(&peer, read, wayland, Arc::clone(&dedup), max_frame_size, outgoing_tx) => {
             log::info!("handle_input for {peer} terminated");
             err
         },
-        err = handle_output(&peer, write, wayland) => {
+        err = handle_output(&peer, write, wayland, &selections, Arc::clone(&dedup), strip_image_metadata, max_frame_size, outgoing_rx) => {
             log::info!("handle_output for {peer} terminated");
             err
         }
@@ -153,16 +500,49 @@ async fn handle_client(peer: impl Display, mut stream: TcpStream, wayland: bool)
 async fn main() {
     pretty_env_logger::init();
     let args = Args::parse();
+    let selections = args.selections();
     let mut tasks = tokio::task::JoinSet::new();
     match args.command {
         Cmd::Server { port } => {
             let listener = TcpListener::bind(format!("0.0.0.0:{port}")).await.unwrap();
+            let tls_config = tls::server_config(&args.tls).unwrap();
+            let acceptor = tls_config.map(TlsAcceptor::from);
 
             loop {
                 match listener.accept().await {
                     Ok((stream, peer)) => {
                         log::info!("New connection from {peer}");
-                        tasks.spawn(handle_client(peer, stream, args.wayland));
+                        if let Some(acceptor) = acceptor.clone() {
+                            let wayland = args.wayland;
+                            let selections = selections.clone();
+                            let strip_image_metadata = args.strip_image_metadata;
+                            let max_frame_size = args.max_frame_size;
+                            tasks.spawn(async move {
+                                match acceptor.accept(stream).await {
+                                    Ok(stream) => {
+                                        handle_client(
+                                            peer,
+                                            stream,
+                                            wayland,
+                                            selections,
+                                            strip_image_metadata,
+                                            max_frame_size,
+                                        )
+                                        .await
+                                    }
+                                    Err(e) => log::error!("TLS handshake with {peer} failed: {e}"),
+                                }
+                            });
+                        } else {
+                            tasks.spawn(handle_client(
+                                peer,
+                                stream,
+                                args.wayland,
+                                selections.clone(),
+                                args.strip_image_metadata,
+                                args.max_frame_size,
+                            ));
+                        }
                     }
                     Err(e) => log::error!("{e}"),
                 }
@@ -171,7 +551,55 @@ async fn main() {
         Cmd::Client { host } => {
             let stream = TcpStream::connect(&host).await.unwrap();
             log::info!("Connected to {host}");
-            handle_client(host, stream, args.wayland).await;
+            if let Some(client_config) = tls::client_config(&args.tls).unwrap() {
+                let connector = TlsConnector::from(client_config);
+                let server_name = host
+                    .rsplit_once(':')
+                    .map_or(host.as_str(), |(name, _)| name)
+                    .to_owned()
+                    .try_into()
+                    .expect("invalid TLS server name");
+                let stream = connector.connect(server_name, stream).await.unwrap();
+                handle_client(
+                    host,
+                    stream,
+                    args.wayland,
+                    selections,
+                    args.strip_image_metadata,
+                    args.max_frame_size,
+                )
+                .await;
+            } else {
+                handle_client(
+                    host,
+                    stream,
+                    args.wayland,
+                    selections,
+                    args.strip_image_metadata,
+                    args.max_frame_size,
+                )
+                .await;
+            }
+        }
+        Cmd::Rdp {
+            host,
+            username,
+            password,
+            domain,
+        } => {
+            if let Err(e) = cliprdr::run_rdp_bridge(
+                &host,
+                username,
+                password,
+                domain,
+                args.wayland,
+                selections,
+                args.strip_image_metadata,
+            )
+            .await
+            {
+                log::error!("{e}");
+            }
         }
     }
 }
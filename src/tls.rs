@@ -0,0 +1,93 @@
+use std::{fs::File, io::BufReader, path::Path, sync::Arc};
+
+use tokio_rustls::rustls::{
+    self,
+    pki_types::{CertificateDer, PrivateKeyDer},
+};
+
+/// Command-line knobs for enabling TLS/mTLS on the sync connection.
+///
+/// All fields are optional: with nothing set, `clipport` falls back to a
+/// plain `TcpStream`. A server becomes TLS-enabled once `tls_cert`/`tls_key`
+/// are both set; a client becomes TLS-enabled once `ca` is set (so it has
+/// something to verify the server's certificate against).
+#[derive(clap::Args, Clone, Default)]
+pub struct TlsArgs {
+    /// Path to the PEM-encoded certificate chain used to identify this peer.
+    #[arg(long)]
+    pub tls_cert: Option<std::path::PathBuf>,
+    /// Path to the PEM-encoded private key matching `--tls-cert`.
+    #[arg(long)]
+    pub tls_key: Option<std::path::PathBuf>,
+    /// Path to a PEM-encoded CA certificate used to verify the peer.
+    #[arg(long)]
+    pub ca: Option<std::path::PathBuf>,
+    /// Require the client to present a certificate signed by `--ca` (mTLS).
+    #[arg(long)]
+    pub require_client_cert: bool,
+}
+
+fn load_certs(path: &Path) -> anyhow::Result<Vec<CertificateDer<'static>>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(Into::into)
+}
+
+fn load_key(path: &Path) -> anyhow::Result<PrivateKeyDer<'static>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .ok_or_else(|| anyhow::anyhow!("no private key found in {}", path.display()))
+}
+
+fn load_root_store(path: &Path) -> anyhow::Result<rustls::RootCertStore> {
+    let mut store = rustls::RootCertStore::empty();
+    for cert in load_certs(path)? {
+        store.add(cert)?;
+    }
+    Ok(store)
+}
+
+/// Builds a server-side rustls config from `args`, or returns `None` if TLS
+/// was not requested (no `--tls-cert`/`--tls-key` given).
+pub fn server_config(args: &TlsArgs) -> anyhow::Result<Option<Arc<rustls::ServerConfig>>> {
+    let (Some(cert_path), Some(key_path)) = (&args.tls_cert, &args.tls_key) else {
+        return Ok(None);
+    };
+    let certs = load_certs(cert_path)?;
+    let key = load_key(key_path)?;
+
+    let verifier = if args.require_client_cert {
+        let ca = args
+            .ca
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--require-client-cert needs --ca"))?;
+        rustls::server::WebPkiClientVerifier::builder(Arc::new(load_root_store(ca)?)).build()?
+    } else {
+        rustls::server::WebPkiClientVerifier::no_client_auth()
+    };
+
+    let config = rustls::ServerConfig::builder()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(certs, key)?;
+    Ok(Some(Arc::new(config)))
+}
+
+/// Builds a client-side rustls config from `args`, or returns `None` if TLS
+/// was not requested (no `--ca` given to verify the server against).
+pub fn client_config(args: &TlsArgs) -> anyhow::Result<Option<Arc<rustls::ClientConfig>>> {
+    let Some(ca_path) = &args.ca else {
+        return Ok(None);
+    };
+    let root_store = load_root_store(ca_path)?;
+    let builder = rustls::ClientConfig::builder().with_root_certificates(root_store);
+
+    let config = if let (Some(cert_path), Some(key_path)) = (&args.tls_cert, &args.tls_key) {
+        let certs = load_certs(cert_path)?;
+        let key = load_key(key_path)?;
+        builder.with_client_auth_cert(certs, key)?
+    } else {
+        builder.with_no_client_auth()
+    };
+    Ok(Some(Arc::new(config)))
+}